@@ -0,0 +1,249 @@
+use serde::Serialize;
+
+/// A complete, checksum-validated binary frame extracted from the stream.
+#[derive(Debug, Clone, Serialize)]
+pub struct FrameResult {
+    pub class: u8,
+    pub id: u8,
+    pub payload: Vec<u8>,
+}
+
+/// Layout of a binary framed protocol: `sync ++ header ++ length(LE u16) ++ payload ++
+/// checksum(2 bytes)`. `class_offset`/`id_offset` and `length_offset` are each how many header
+/// bytes (after `sync`) precede that field, and `checksum` computes the trailing two checksum
+/// bytes over `class..payload`, so protocols that order their header fields differently or use a
+/// different checksum can still reuse the parser.
+#[derive(Debug, Clone)]
+pub struct FrameConfig {
+    pub sync: Vec<u8>,
+    pub class_offset: usize,
+    pub id_offset: usize,
+    pub length_offset: usize,
+    pub checksum: fn(&[u8]) -> (u8, u8),
+}
+
+impl Default for FrameConfig {
+    /// The GNSS receiver framing this parser was written for: `sync ++ class ++ id ++
+    /// length(LE u16) ++ payload ++ checksum`, with an 8-bit Fletcher checksum over `class` through
+    /// the end of `payload`.
+    fn default() -> Self {
+        Self {
+            sync: vec![0xB5, 0x62],
+            class_offset: 0,
+            id_offset: 1,
+            length_offset: 2,
+            checksum: fletcher8,
+        }
+    }
+}
+
+/// Stateful extractor that scans a byte buffer for complete frames, validating each against
+/// `config`'s checksum before yielding it. Bytes that don't form a valid frame (a false sync
+/// match or a bad checksum) are discarded one at a time and counted in `skipped`, so callers can
+/// surface stream corruption.
+pub struct Parser {
+    config: FrameConfig,
+    skipped: u64,
+}
+
+impl Parser {
+    pub fn new(config: FrameConfig) -> Self {
+        Self { config, skipped: 0 }
+    }
+
+    /// Number of bytes discarded so far while resyncing after a false sync or checksum failure.
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// Drain every complete, valid frame currently available in `buffer`, removing their bytes.
+    /// Leaves a trailing partial frame in place for the next call, and never discards a `\n` or
+    /// anything before it as skipped noise, so a connection that only ever speaks a
+    /// newline-delimited text protocol (or mixes it with framed binary data) is left untouched for
+    /// `drain_lines` instead of having its lines eaten as stream corruption.
+    pub fn drain(&mut self, buffer: &mut Vec<u8>) -> Vec<FrameResult> {
+        let mut frames = Vec::new();
+
+        loop {
+            let sync_pos = find_sync(buffer, &self.config.sync);
+
+            // A newline before any sync match (or with no sync in the buffer at all) means this
+            // is ordinary line-oriented text, not frame noise — leave it for the caller's line
+            // draining instead of discarding it as skipped.
+            if let Some(newline_pos) = buffer.iter().position(|&b| b == b'\n') {
+                if sync_pos.map_or(true, |pos| newline_pos < pos) {
+                    break;
+                }
+            }
+
+            let sync_pos = match sync_pos {
+                Some(pos) => pos,
+                None => {
+                    // No sync and no newline in the buffer: these bytes may be the start of a
+                    // text line whose `\n` hasn't arrived yet, so keep them all for the next call.
+                    break;
+                }
+            };
+
+            if sync_pos > 0 {
+                buffer.drain(..sync_pos);
+                self.skipped += sync_pos as u64;
+            }
+
+            // sync ++ header (class/id/length in config-defined positions)
+            let header_len = self.config.sync.len() + self.config.length_offset + 2;
+            if buffer.len() < header_len {
+                break; // wait for the rest of the header
+            }
+
+            let length_pos = self.config.sync.len() + self.config.length_offset;
+            let length =
+                u16::from_le_bytes([buffer[length_pos], buffer[length_pos + 1]]) as usize;
+            let frame_len = header_len + length + 2; // + 2 checksum bytes
+
+            if buffer.len() < frame_len {
+                break; // wait for the rest of the payload + checksum
+            }
+
+            let body = &buffer[self.config.sync.len()..frame_len - 2];
+            let (ck_a, ck_b) = (self.config.checksum)(body);
+
+            if ck_a == buffer[frame_len - 2] && ck_b == buffer[frame_len - 1] {
+                let class = buffer[self.config.sync.len() + self.config.class_offset];
+                let id = buffer[self.config.sync.len() + self.config.id_offset];
+                let payload = buffer[header_len..header_len + length].to_vec();
+                buffer.drain(..frame_len);
+                frames.push(FrameResult { class, id, payload });
+            } else {
+                // False sync match: drop the leading sync byte and let the next loop iteration
+                // look for the next occurrence.
+                buffer.drain(..1);
+                self.skipped += 1;
+            }
+        }
+
+        frames
+    }
+}
+
+fn find_sync(buffer: &[u8], sync: &[u8]) -> Option<usize> {
+    buffer.windows(sync.len()).position(|w| w == sync)
+}
+
+/// 8-bit Fletcher checksum, the default for `FrameConfig`: `CK_A` accumulates each byte, `CK_B`
+/// accumulates the running `CK_A`, both wrapping at 256.
+fn fletcher8(data: &[u8]) -> (u8, u8) {
+    let mut ck_a: u8 = 0;
+    let mut ck_b: u8 = 0;
+    for &byte in data {
+        ck_a = ck_a.wrapping_add(byte);
+        ck_b = ck_b.wrapping_add(ck_a);
+    }
+    (ck_a, ck_b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_default_frame(class: u8, id: u8, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0xB5, 0x62, class, id];
+        frame.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        frame.extend_from_slice(payload);
+        let (ck_a, ck_b) = fletcher8(&frame[2..]);
+        frame.push(ck_a);
+        frame.push(ck_b);
+        frame
+    }
+
+    #[test]
+    fn drains_a_valid_frame_and_removes_its_bytes() {
+        let mut parser = Parser::new(FrameConfig::default());
+        let mut buffer = encode_default_frame(0x01, 0x02, &[0xAA, 0xBB]);
+
+        let frames = parser.drain(&mut buffer);
+
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].class, 0x01);
+        assert_eq!(frames[0].id, 0x02);
+        assert_eq!(frames[0].payload, vec![0xAA, 0xBB]);
+        assert!(buffer.is_empty());
+        assert_eq!(parser.skipped(), 0);
+    }
+
+    #[test]
+    fn resyncs_past_a_corrupted_frame() {
+        let mut parser = Parser::new(FrameConfig::default());
+        let mut buffer = encode_default_frame(0x03, 0x04, &[0xFF]);
+        if let Some(last) = buffer.last_mut() {
+            *last ^= 0xFF; // corrupt CK_B so the first frame fails validation
+        }
+        buffer.append(&mut encode_default_frame(0x01, 0x02, &[0xAA]));
+
+        let frames = parser.drain(&mut buffer);
+
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].class, 0x01);
+        assert!(parser.skipped() > 0);
+    }
+
+    #[test]
+    fn leaves_ascii_lines_untouched_when_no_sync_is_present() {
+        let mut parser = Parser::new(FrameConfig::default());
+        let mut buffer = b"HELLO WORLD\n".to_vec();
+
+        let frames = parser.drain(&mut buffer);
+
+        assert!(frames.is_empty());
+        assert_eq!(buffer, b"HELLO WORLD\n");
+        assert_eq!(parser.skipped(), 0);
+    }
+
+    #[test]
+    fn leaves_text_preceding_a_frame_for_the_caller_to_drain_first() {
+        let mut parser = Parser::new(FrameConfig::default());
+        let mut buffer = b"HELLO\n".to_vec();
+        buffer.extend(encode_default_frame(0x01, 0x02, &[0xAA]));
+
+        let frames = parser.drain(&mut buffer);
+        assert!(frames.is_empty());
+        assert!(buffer.starts_with(b"HELLO\n"));
+
+        // Once the caller strips the line (as drain_lines would), the frame parses normally.
+        buffer.drain(.."HELLO\n".len());
+        let frames = parser.drain(&mut buffer);
+
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].class, 0x01);
+    }
+
+    #[test]
+    fn respects_configurable_checksum_and_offsets() {
+        fn xor_checksum(data: &[u8]) -> (u8, u8) {
+            (data.iter().fold(0u8, |acc, &b| acc ^ b), 0)
+        }
+        let config = FrameConfig {
+            sync: vec![0xAA],
+            class_offset: 1,
+            id_offset: 0,
+            length_offset: 2,
+            checksum: xor_checksum,
+        };
+
+        // sync(1) ++ id(1) ++ class(1) ++ length(LE u16) ++ payload ++ checksum(2)
+        let mut buffer = vec![0xAA, 0x02, 0x01];
+        buffer.extend_from_slice(&1u16.to_le_bytes());
+        buffer.push(0x99);
+        let (ck_a, ck_b) = xor_checksum(&buffer[1..]);
+        buffer.push(ck_a);
+        buffer.push(ck_b);
+
+        let mut parser = Parser::new(config);
+        let frames = parser.drain(&mut buffer);
+
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].class, 0x01);
+        assert_eq!(frames[0].id, 0x02);
+        assert_eq!(frames[0].payload, vec![0x99]);
+    }
+}
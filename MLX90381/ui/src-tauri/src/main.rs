@@ -1,7 +1,10 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod frame_parser;
+mod serial_backend;
 mod serial_handler;
+mod session_log;
 
 use serial_handler::SerialState;
 use std::sync::Arc;
@@ -14,11 +17,17 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             serial_handler::list_ports,
             serial_handler::connect_port,
+            serial_handler::connect_mock,
             serial_handler::disconnect_port,
             serial_handler::send_command,
+            serial_handler::send_command_and_await,
             serial_handler::send_memory_sequence,
             serial_handler::read_incoming,
             serial_handler::is_connected,
+            serial_handler::start_recording,
+            serial_handler::stop_recording,
+            serial_handler::export_session,
+            serial_handler::replay_session,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
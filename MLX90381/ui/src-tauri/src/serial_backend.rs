@@ -0,0 +1,203 @@
+use serialport::SerialPort;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+
+/// Whatever a connection's `Connection.port` talks to: a real serial port, or (for tests/demos) the in-memory
+/// loopback below. Lets the rest of the app stay agnostic of which one is plugged in.
+pub trait SerialBackend: Read + Write + Send {
+    /// Open a second handle onto the same underlying connection, for the background reader task.
+    fn try_clone_backend(&self) -> io::Result<Box<dyn SerialBackend>>;
+}
+
+/// Adapts a `serialport::SerialPort` to `SerialBackend`.
+pub struct RealPort(Box<dyn SerialPort>);
+
+impl RealPort {
+    pub fn new(port: Box<dyn SerialPort>) -> Self {
+        Self(port)
+    }
+}
+
+impl Read for RealPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Write for RealPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl SerialBackend for RealPort {
+    fn try_clone_backend(&self) -> io::Result<Box<dyn SerialBackend>> {
+        let cloned = self
+            .0
+            .try_clone()
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+        Ok(Box::new(RealPort(cloned)))
+    }
+}
+
+/// In-memory loopback backend: bytes the app writes are fed to a scripted
+/// [`MemoryProtocolResponder`] instead of a real board, and whatever it decides to reply with
+/// lands in the same inbox the app reads from. Lets the whole UI — including
+/// `send_memory_sequence` and `read_incoming` — be exercised without a physical port.
+#[derive(Clone)]
+pub struct MockPort {
+    inbox: Arc<Mutex<VecDeque<u8>>>,
+    responder: Arc<Mutex<MemoryProtocolResponder>>,
+}
+
+impl MockPort {
+    pub fn new() -> Self {
+        Self {
+            inbox: Arc::new(Mutex::new(VecDeque::new())),
+            responder: Arc::new(Mutex::new(MemoryProtocolResponder::new())),
+        }
+    }
+}
+
+impl Read for MockPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut inbox = self.inbox.lock().unwrap();
+        if inbox.is_empty() {
+            // Mirrors the real port's behavior of timing out when nothing is available.
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "no data available"));
+        }
+        let n = buf.len().min(inbox.len());
+        for slot in buf.iter_mut().take(n) {
+            *slot = inbox.pop_front().unwrap();
+        }
+        Ok(n)
+    }
+}
+
+impl Write for MockPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut responder = self.responder.lock().unwrap();
+        let mut inbox = self.inbox.lock().unwrap();
+        for &byte in buf {
+            responder.feed(byte, &mut inbox);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SerialBackend for MockPort {
+    fn try_clone_backend(&self) -> io::Result<Box<dyn SerialBackend>> {
+        Ok(Box::new(self.clone()))
+    }
+}
+
+/// Understands enough of the `W`/`E` memory programming protocol (command letter, then repeated
+/// 5-char decimal words each confirmed with `y`) to echo back plausible acknowledgements.
+struct MemoryProtocolResponder {
+    state: ResponderState,
+}
+
+enum ResponderState {
+    AwaitingCommand,
+    AwaitingWord { digits: String },
+    AwaitingConfirm { word: String },
+}
+
+impl MemoryProtocolResponder {
+    fn new() -> Self {
+        Self {
+            state: ResponderState::AwaitingCommand,
+        }
+    }
+
+    fn feed(&mut self, byte: u8, inbox: &mut VecDeque<u8>) {
+        match &mut self.state {
+            ResponderState::AwaitingCommand => {
+                if byte == b'W' || byte == b'E' {
+                    self.state = ResponderState::AwaitingWord {
+                        digits: String::new(),
+                    };
+                }
+            }
+            ResponderState::AwaitingWord { digits } => {
+                if byte.is_ascii_digit() || byte == b' ' {
+                    digits.push(byte as char);
+                    if digits.len() == 5 {
+                        let word = digits.trim().to_string();
+                        // Echo the word back as soon as it's received, the way a real board
+                        // acks a word before the 'y' confirmation is sent.
+                        inbox.extend(word.as_bytes());
+                        inbox.push_back(b'\n');
+                        self.state = ResponderState::AwaitingConfirm { word };
+                    }
+                }
+            }
+            ResponderState::AwaitingConfirm { .. } => {
+                if byte == b'y' {
+                    self.state = ResponderState::AwaitingWord {
+                        digits: String::new(),
+                    };
+                } else {
+                    self.state = ResponderState::AwaitingCommand;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn echoes_each_word_back_before_the_confirm_byte() {
+        let mut mock = MockPort::new();
+        let mut reply = [0u8; 64];
+
+        mock.write(b"W").unwrap();
+        mock.write(b"12345").unwrap();
+        let n = mock.read(&mut reply).unwrap();
+        assert_eq!(&reply[..n], b"12345\n");
+
+        mock.write(b"y").unwrap();
+        mock.write(b"67890").unwrap();
+        let n = mock.read(&mut reply).unwrap();
+        assert_eq!(&reply[..n], b"67890\n");
+    }
+
+    #[test]
+    fn a_non_confirm_byte_resets_to_awaiting_command() {
+        let mut mock = MockPort::new();
+        let mut reply = [0u8; 64];
+
+        mock.write(b"W").unwrap();
+        mock.write(b"12345").unwrap();
+        let n = mock.read(&mut reply).unwrap();
+        assert_eq!(&reply[..n], b"12345\n");
+
+        // Anything other than 'y' aborts back to AwaitingCommand, so a bare digit sequence with
+        // no leading W/E is ignored rather than echoed.
+        mock.write(b"n").unwrap();
+        mock.write(b"99999").unwrap();
+        assert!(mock.read(&mut reply).is_err());
+    }
+
+    #[test]
+    fn read_times_out_when_the_inbox_is_empty() {
+        let mut mock = MockPort::new();
+        let mut buf = [0u8; 8];
+
+        let err = mock.read(&mut buf).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+}
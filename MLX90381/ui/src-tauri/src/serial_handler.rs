@@ -1,21 +1,67 @@
+use crate::frame_parser::{self, FrameConfig};
+use crate::serial_backend::{MockPort, RealPort, SerialBackend};
+use crate::session_log::{self, Direction};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use serialport::{SerialPort, SerialPortInfo};
+use serialport::SerialPortInfo;
+use std::collections::HashMap;
 use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tauri::State;
-use tokio::sync::Mutex;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::{oneshot, Mutex};
 
-pub struct SerialState {
-    port: Option<Box<dyn SerialPort>>,
+/// One open connection, keyed by a user-supplied connection id so the app can talk to several
+/// boards (e.g. an ADCS controller and a sensor board) at once.
+struct Connection {
+    /// Writer handle used for `send_command` / `send_memory_sequence`. The background reader
+    /// holds a `try_clone_backend()` of this same underlying port, so writes never block on the
+    /// read loop.
+    port: Option<Box<dyn SerialBackend>>,
     buffer: Vec<u8>,
+    /// Set to `true` to ask the background reader task to stop; checked once per poll iteration.
+    reader_cancel: Option<Arc<AtomicBool>>,
+    /// Extracts UBX-style binary frames out of `buffer` alongside the newline-delimited text.
+    frame_parser: frame_parser::Parser,
+    /// Requests registered by `send_command_and_await`, waiting for a matching line from the
+    /// reader task.
+    pending: Vec<PendingRequest>,
+    next_request_id: u64,
+    /// Active session recorder, if `start_recording` has been called and `stop_recording` hasn't.
+    recorder: Option<Arc<session_log::SessionRecorder>>,
 }
 
-impl SerialState {
-    pub fn new() -> Self {
+impl Connection {
+    fn new() -> Self {
         Self {
             port: None,
             buffer: Vec::new(),
+            reader_cancel: None,
+            frame_parser: frame_parser::Parser::new(FrameConfig::default()),
+            pending: Vec::new(),
+            next_request_id: 0,
+            recorder: None,
+        }
+    }
+}
+
+/// A `send_command_and_await` call waiting on the next line that matches `pattern`.
+struct PendingRequest {
+    id: u64,
+    pattern: Regex,
+    responder: oneshot::Sender<String>,
+}
+
+/// All connections currently open, keyed by connection id.
+pub struct SerialState {
+    connections: HashMap<String, Connection>,
+}
+
+impl SerialState {
+    pub fn new() -> Self {
+        Self {
+            connections: HashMap::new(),
         }
     }
 }
@@ -55,27 +101,51 @@ pub async fn list_ports() -> Vec<PortInfo> {
         .collect()
 }
 
-/// Connect to a serial port
+/// Connect to a serial port under `connection_id` and start its background reader task.
+///
+/// On success, spawns a task that owns a cloned read handle, continuously drains the port into
+/// the connection's buffer, and emits a `serial-line:{connection_id}` event for every complete
+/// line. `read_incoming` remains available as a fallback for callers that still want to poll.
 #[tauri::command]
 pub async fn connect_port(
+    app_handle: AppHandle,
     state: State<'_, Arc<Mutex<SerialState>>>,
+    connection_id: String,
     port_name: String,
     baud_rate: u32,
 ) -> Result<SerialResult, String> {
+    let state_handle = Arc::clone(state.inner());
     let mut state = state.lock().await;
 
-    // Close existing connection
-    if state.port.is_some() {
-        state.port = None;
+    // Close any existing connection under this id and stop its reader task, if any.
+    if let Some(mut existing) = state.connections.remove(&connection_id) {
+        stop_reader(&mut existing);
     }
 
     match serialport::new(&port_name, baud_rate)
-        .timeout(Duration::from_millis(10))
+        .timeout(Duration::from_millis(100))
         .open()
     {
         Ok(port) => {
-            state.port = Some(port);
-            state.buffer.clear();
+            let port: Box<dyn SerialBackend> = Box::new(RealPort::new(port));
+            let reader_port = match port.try_clone_backend() {
+                Ok(clone) => clone,
+                Err(e) => {
+                    return Ok(SerialResult {
+                        success: false,
+                        message: format!("Failed to clone port for reader task: {}", e),
+                    })
+                }
+            };
+
+            let cancel = Arc::new(AtomicBool::new(false));
+            let mut connection = Connection::new();
+            connection.port = Some(port);
+            connection.reader_cancel = Some(Arc::clone(&cancel));
+            state.connections.insert(connection_id.clone(), connection);
+
+            spawn_reader_task(app_handle, state_handle, connection_id, reader_port, cancel);
+
             Ok(SerialResult {
                 success: true,
                 message: format!("Connected to {} @ {}", port_name, baud_rate),
@@ -88,148 +158,804 @@ pub async fn connect_port(
     }
 }
 
-/// Disconnect from serial port
+/// Connect `connection_id` to the in-memory loopback backend instead of a physical port, so the
+/// UI (including `send_memory_sequence` and `read_incoming`) can be exercised in CI and demos.
+#[tauri::command]
+pub async fn connect_mock(
+    app_handle: AppHandle,
+    state: State<'_, Arc<Mutex<SerialState>>>,
+    connection_id: String,
+) -> Result<SerialResult, String> {
+    let state_handle = Arc::clone(state.inner());
+    let mut state = state.lock().await;
+
+    if let Some(mut existing) = state.connections.remove(&connection_id) {
+        stop_reader(&mut existing);
+    }
+
+    let mock = MockPort::new();
+    let reader_port = match mock.try_clone_backend() {
+        Ok(clone) => clone,
+        Err(e) => {
+            return Ok(SerialResult {
+                success: false,
+                message: format!("Failed to clone mock port for reader task: {}", e),
+            })
+        }
+    };
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let mut connection = Connection::new();
+    connection.port = Some(Box::new(mock));
+    connection.reader_cancel = Some(Arc::clone(&cancel));
+    state.connections.insert(connection_id.clone(), connection);
+
+    spawn_reader_task(app_handle, state_handle, connection_id, reader_port, cancel);
+
+    Ok(SerialResult {
+        success: true,
+        message: "Connected to mock loopback port".to_string(),
+    })
+}
+
+/// Spawn the long-lived background reader for one connection: reads into its buffer, splits
+/// complete lines and frames, and emits them on that connection's own events. Runs on the
+/// blocking pool since backend reads are blocking.
+///
+/// If the read itself errors out (e.g. the device was unplugged) rather than just timing out, the
+/// loop exits and the connection's port is torn down so `is_connected` stops reporting a link
+/// that's no longer there; a `serial-error:{connection_id}` event is emitted so the UI can show it.
+fn spawn_reader_task(
+    app_handle: AppHandle,
+    state: Arc<Mutex<SerialState>>,
+    connection_id: String,
+    mut reader: Box<dyn SerialBackend>,
+    cancel: Arc<AtomicBool>,
+) {
+    tokio::task::spawn_blocking(move || {
+        let mut buf = [0u8; 256];
+        let mut last_skipped = 0u64;
+        let mut read_error: Option<std::io::Error> = None;
+        while !cancel.load(Ordering::Relaxed) {
+            match reader.read(&mut buf) {
+                Ok(0) => continue,
+                Ok(n) => {
+                    let (frames, lines, skipped, recorder) = {
+                        let mut state = state.blocking_lock();
+                        let connection = match state.connections.get_mut(&connection_id) {
+                            Some(connection) => connection,
+                            None => break, // connection was torn down from under us
+                        };
+                        connection.buffer.extend_from_slice(&buf[..n]);
+                        let frames = connection.frame_parser.drain(&mut connection.buffer);
+                        let lines = drain_lines(&mut connection.buffer);
+                        for line in &lines {
+                            if let Some(idx) = connection
+                                .pending
+                                .iter()
+                                .position(|r| r.pattern.is_match(line))
+                            {
+                                let pending = connection.pending.remove(idx);
+                                let _ = pending.responder.send(line.clone());
+                            }
+                        }
+                        (
+                            frames,
+                            lines,
+                            connection.frame_parser.skipped(),
+                            connection.recorder.clone(),
+                        )
+                    };
+                    if let Some(recorder) = recorder {
+                        let raw = buf[..n].to_vec();
+                        let decoded = (!lines.is_empty()).then(|| lines.join("\n"));
+                        tokio::spawn(async move {
+                            let _ = recorder
+                                .log(Direction::Rx, &raw, decoded.as_deref())
+                                .await;
+                        });
+                    }
+                    for frame in frames {
+                        let _ = app_handle.emit(&format!("serial-frame:{}", connection_id), frame);
+                    }
+                    for line in lines {
+                        let _ = app_handle.emit(&format!("serial-line:{}", connection_id), line);
+                    }
+                    if skipped > last_skipped {
+                        let _ = app_handle.emit(
+                            &format!("serial-frame-error:{}", connection_id),
+                            skipped - last_skipped,
+                        );
+                        last_skipped = skipped;
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => continue,
+                Err(e) => {
+                    read_error = Some(e);
+                    break;
+                }
+            }
+        }
+
+        if let Some(e) = read_error {
+            let mut state = state.blocking_lock();
+            if let Some(connection) = state.connections.get_mut(&connection_id) {
+                stop_reader(connection);
+            }
+            drop(state);
+            let _ = app_handle.emit(&format!("serial-error:{}", connection_id), e.to_string());
+        }
+    });
+}
+
+/// Extract and trim all complete (`\n`-terminated) lines currently buffered.
+fn drain_lines(buffer: &mut Vec<u8>) -> Vec<String> {
+    let mut lines = Vec::new();
+    while let Some(pos) = buffer.iter().position(|&b| b == b'\n') {
+        let line_bytes: Vec<u8> = buffer.drain(..=pos).collect();
+        let line = String::from_utf8_lossy(&line_bytes)
+            .trim_end_matches(&['\r', '\n'][..])
+            .to_string();
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+/// Signal a connection's background reader to stop and drop its writer handle. Does not block on
+/// the task exiting; the next `read()` timeout inside the task notices the cancellation.
+fn stop_reader(connection: &mut Connection) {
+    if let Some(cancel) = connection.reader_cancel.take() {
+        cancel.store(true, Ordering::Relaxed);
+    }
+    connection.port = None;
+}
+
+/// Disconnect `connection_id`, stopping its reader task and dropping its state entirely.
 #[tauri::command]
 pub async fn disconnect_port(
     state: State<'_, Arc<Mutex<SerialState>>>,
+    connection_id: String,
 ) -> Result<SerialResult, String> {
     let mut state = state.lock().await;
-    state.port = None;
-    state.buffer.clear();
+    if let Some(mut connection) = state.connections.remove(&connection_id) {
+        stop_reader(&mut connection);
+    }
     Ok(SerialResult {
         success: true,
         message: "Disconnected".to_string(),
     })
 }
 
-/// Check if connected
+/// Check if `connection_id` is connected
 #[tauri::command]
-pub async fn is_connected(state: State<'_, Arc<Mutex<SerialState>>>) -> Result<bool, String> {
+pub async fn is_connected(
+    state: State<'_, Arc<Mutex<SerialState>>>,
+    connection_id: String,
+) -> Result<bool, String> {
     let state = state.lock().await;
-    Ok(state.port.is_some())
+    Ok(state
+        .connections
+        .get(&connection_id)
+        .map_or(false, |c| c.port.is_some()))
 }
 
-/// Send a single character command
+/// Send a single character command on `connection_id`
 #[tauri::command]
 pub async fn send_command(
     state: State<'_, Arc<Mutex<SerialState>>>,
+    connection_id: String,
     command: String,
 ) -> Result<SerialResult, String> {
     let mut state = state.lock().await;
 
-    if let Some(ref mut port) = state.port {
-        match port.write(command.as_bytes()) {
-            Ok(_) => Ok(SerialResult {
-                success: true,
-                message: format!("Sent: {}", command),
-            }),
-            Err(e) => Ok(SerialResult {
+    let connection = match state.connections.get_mut(&connection_id) {
+        Some(connection) if connection.port.is_some() => connection,
+        _ => {
+            return Ok(SerialResult {
+                success: false,
+                message: "Not connected".to_string(),
+            })
+        }
+    };
+
+    let recorder = match write_port(connection, command.as_bytes()) {
+        Ok(recorder) => recorder,
+        Err(e) => {
+            return Ok(SerialResult {
                 success: false,
                 message: format!("Write failed: {}", e),
-            }),
+            })
         }
-    } else {
-        Ok(SerialResult {
+    };
+    drop(state);
+
+    log_tx(recorder.as_ref(), command.as_bytes()).await;
+    Ok(SerialResult {
+        success: true,
+        message: format!("Sent: {}", command),
+    })
+}
+
+/// Record a TX event if a session recorder is active. Best-effort: a logging failure shouldn't
+/// fail the command that triggered it. Takes an already-cloned recorder handle rather than
+/// borrowing the connection, so callers await the log insert after releasing the state lock
+/// instead of serializing every other connection's commands behind one connection's DB write.
+async fn log_tx(recorder: Option<&Arc<session_log::SessionRecorder>>, raw: &[u8]) {
+    if let Some(recorder) = recorder {
+        let _ = recorder.log(Direction::Tx, raw, None).await;
+    }
+}
+
+/// Write to the connection's port, returning a clone of its active recorder (if any) so the
+/// caller can log the bytes with `log_tx` once it has released the state lock.
+fn write_port(
+    connection: &mut Connection,
+    bytes: &[u8],
+) -> std::io::Result<Option<Arc<session_log::SessionRecorder>>> {
+    let port = connection
+        .port
+        .as_mut()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotConnected, "Not connected"))?;
+    port.write(bytes)?;
+    Ok(connection.recorder.clone())
+}
+
+#[derive(Serialize)]
+pub struct AwaitResult {
+    success: bool,
+    response: Option<String>,
+    message: String,
+}
+
+/// Send a command on `connection_id` and wait for its reader task to observe a line matching
+/// `pattern` (a regex; plain terminators like `"OK"` are valid patterns too), instead of racing
+/// `read_incoming`.
+///
+/// Writes and then registers a oneshot responder in the connection's pending list without
+/// releasing the state lock in between, so the reader task can't process a reply before the
+/// registration exists; the next matching line it sees completes this call directly.
+/// If no line matches within `timeout_ms`, the pending registration is removed and an error
+/// result is returned.
+#[tauri::command]
+pub async fn send_command_and_await(
+    state: State<'_, Arc<Mutex<SerialState>>>,
+    connection_id: String,
+    command: String,
+    pattern: String,
+    timeout_ms: u64,
+) -> Result<AwaitResult, String> {
+    let regex = Regex::new(&pattern).map_err(|e| format!("Invalid pattern: {}", e))?;
+
+    let (id, rx, recorder) = {
+        let mut state = state.lock().await;
+
+        let connection = match state.connections.get_mut(&connection_id) {
+            Some(connection) if connection.port.is_some() => connection,
+            _ => {
+                return Ok(AwaitResult {
+                    success: false,
+                    response: None,
+                    message: "Not connected".to_string(),
+                })
+            }
+        };
+
+        let recorder = match write_port(connection, command.as_bytes()) {
+            Ok(recorder) => recorder,
+            Err(e) => {
+                return Ok(AwaitResult {
+                    success: false,
+                    response: None,
+                    message: format!("Write failed: {}", e),
+                })
+            }
+        };
+
+        let id = connection.next_request_id;
+        connection.next_request_id += 1;
+        let (tx, rx) = oneshot::channel();
+        connection.pending.push(PendingRequest {
+            id,
+            pattern: regex,
+            responder: tx,
+        });
+        (id, rx, recorder)
+    };
+    log_tx(recorder.as_ref(), command.as_bytes()).await;
+
+    match tokio::time::timeout(Duration::from_millis(timeout_ms), rx).await {
+        Ok(Ok(line)) => Ok(AwaitResult {
+            success: true,
+            response: Some(line),
+            message: "Matched response".to_string(),
+        }),
+        Ok(Err(_)) => Ok(AwaitResult {
             success: false,
-            message: "Not connected".to_string(),
-        })
+            response: None,
+            message: "Reader task stopped before a match arrived".to_string(),
+        }),
+        Err(_) => {
+            // Remove the stale registration so a late match doesn't try to complete a call
+            // that's already timed out.
+            let mut state = state.lock().await;
+            if let Some(connection) = state.connections.get_mut(&connection_id) {
+                connection.pending.retain(|r| r.id != id);
+            }
+            Ok(AwaitResult {
+                success: false,
+                response: None,
+                message: format!("Timed out after {}ms waiting for a match", timeout_ms),
+            })
+        }
     }
 }
 
-/// Send memory programming sequence (W or E command with 8 words)
+/// How many times in all (first send included) a single word is sent before
+/// `send_memory_sequence` gives up on it.
+const MAX_WORD_ATTEMPTS: u32 = 3;
+/// How long to wait for a word's ack before retransmitting, unless the caller overrides it.
+const DEFAULT_ACK_TIMEOUT_MS: u64 = 300;
+
+#[derive(Serialize)]
+pub struct WordAckStatus {
+    index: usize,
+    acked: bool,
+    attempts: u32,
+    message: String,
+}
+
+#[derive(Serialize)]
+pub struct MemorySequenceResult {
+    success: bool,
+    message: String,
+    words: Vec<WordAckStatus>,
+}
+
+/// Send memory programming sequence (W or E command with 8 words) on `connection_id`.
+///
+/// Each word is written, then the call waits for the device to ack it (by default, any line made
+/// up only of digits, such as the device echoing the word back; the digits are not compared with
+/// the word sent; `ack_pattern` can override this with another regex) before sending the `y`
+/// confirmation. A word that isn't acked within `timeout_ms` (default `DEFAULT_ACK_TIMEOUT_MS`)
+/// is retransmitted, up to `MAX_WORD_ATTEMPTS` sends in all; if it still isn't
+/// acked, the sequence aborts and reports exactly which word index failed, alongside the
+/// per-word ack status for everything sent so far.
 #[tauri::command]
 pub async fn send_memory_sequence(
     state: State<'_, Arc<Mutex<SerialState>>>,
+    connection_id: String,
     command: String,
     words: Vec<u16>,
-) -> Result<SerialResult, String> {
-    let mut state = state.lock().await;
-
+    ack_pattern: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<MemorySequenceResult, String> {
     if words.len() != 8 {
-        return Ok(SerialResult {
+        return Ok(MemorySequenceResult {
             success: false,
             message: "Need exactly 8 words".to_string(),
+            words: Vec::new(),
         });
     }
 
-    if let Some(ref mut port) = state.port {
-        // Send initial command (W or E)
-        if let Err(e) = port.write(command.as_bytes()) {
-            return Ok(SerialResult {
-                success: false,
-                message: format!("Failed to send command: {}", e),
-            });
-        }
-        std::thread::sleep(Duration::from_millis(50));
+    let timeout_ms = timeout_ms.unwrap_or(DEFAULT_ACK_TIMEOUT_MS);
+    let ack_pattern = ack_pattern.unwrap_or_else(|| r"^\d+$".to_string());
+    let ack_regex =
+        Regex::new(&ack_pattern).map_err(|e| format!("Invalid ack pattern: {}", e))?;
 
-        // Send each word as 5-char decimal + 'y' to accept
-        for word in words {
-            let field = format!("{:5}", word);
-            if let Err(e) = port.write(field.as_bytes()) {
-                return Ok(SerialResult {
+    let state_handle = Arc::clone(state.inner());
+
+    {
+        let mut state = state.lock().await;
+        let connection = match state.connections.get_mut(&connection_id) {
+            Some(connection) if connection.port.is_some() => connection,
+            _ => {
+                return Ok(MemorySequenceResult {
                     success: false,
-                    message: format!("Failed to send word: {}", e),
-                });
+                    message: "Not connected".to_string(),
+                    words: Vec::new(),
+                })
             }
-            std::thread::sleep(Duration::from_millis(10));
-
-            if let Err(e) = port.write(b"y") {
-                return Ok(SerialResult {
+        };
+        // Send initial command (W or E); this isn't acked individually.
+        let recorder = match write_port(connection, command.as_bytes()) {
+            Ok(recorder) => recorder,
+            Err(e) => {
+                return Ok(MemorySequenceResult {
                     success: false,
-                    message: format!("Failed to send confirm: {}", e),
-                });
+                    message: format!("Failed to send command: {}", e),
+                    words: Vec::new(),
+                })
+            }
+        };
+        drop(state);
+        log_tx(recorder.as_ref(), command.as_bytes()).await;
+    }
+
+    let mut statuses = Vec::with_capacity(words.len());
+
+    for (index, word) in words.into_iter().enumerate() {
+        let field = format!("{:5}", word);
+        let mut attempts = 0;
+        let mut last_error = String::new();
+        let mut acked = false;
+
+        while attempts < MAX_WORD_ATTEMPTS && !acked {
+            attempts += 1;
+
+            match write_and_await_ack(
+                &state_handle,
+                &connection_id,
+                field.as_bytes(),
+                &ack_regex,
+                timeout_ms,
+            )
+            .await
+            {
+                Ok(_) => acked = true,
+                Err(e) => last_error = e,
             }
-            std::thread::sleep(Duration::from_millis(20));
         }
 
-        Ok(SerialResult {
-            success: true,
-            message: "Memory sequence sent".to_string(),
-        })
-    } else {
-        Ok(SerialResult {
-            success: false,
-            message: "Not connected".to_string(),
-        })
+        if acked {
+            // Word accepted; send the confirmation.
+            let mut state = state_handle.lock().await;
+            let connection = match state.connections.get_mut(&connection_id) {
+                Some(connection) if connection.port.is_some() => connection,
+                _ => {
+                    statuses.push(WordAckStatus {
+                        index,
+                        acked: false,
+                        attempts,
+                        message: "Not connected".to_string(),
+                    });
+                    return Ok(MemorySequenceResult {
+                        success: false,
+                        message: format!("Word {} not acknowledged: not connected", index),
+                        words: statuses,
+                    });
+                }
+            };
+            let recorder = match write_port(connection, b"y") {
+                Ok(recorder) => recorder,
+                Err(e) => {
+                    statuses.push(WordAckStatus {
+                        index,
+                        acked: false,
+                        attempts,
+                        message: format!("Failed to send confirm: {}", e),
+                    });
+                    return Ok(MemorySequenceResult {
+                        success: false,
+                        message: format!("Word {} confirm failed: {}", index, e),
+                        words: statuses,
+                    });
+                }
+            };
+            drop(state);
+            log_tx(recorder.as_ref(), b"y").await;
+        }
+
+        statuses.push(WordAckStatus {
+            index,
+            acked,
+            attempts,
+            message: if acked {
+                "Acknowledged".to_string()
+            } else {
+                last_error.clone()
+            },
+        });
+
+        if !acked {
+            return Ok(MemorySequenceResult {
+                success: false,
+                message: format!(
+                    "Word {} not acknowledged after {} attempts: {}",
+                    index, attempts, last_error
+                ),
+                words: statuses,
+            });
+        }
     }
+
+    Ok(MemorySequenceResult {
+        success: true,
+        message: "Memory sequence sent".to_string(),
+        words: statuses,
+    })
 }
 
-/// Read incoming data and return complete lines
+/// Write `bytes` on `connection_id` and wait for the reader task to observe a line matching
+/// `ack_pattern`, registering a oneshot responder the same way `send_command_and_await` does.
+/// Used to ack individual words in `send_memory_sequence` without holding the state lock across
+/// the wait.
+async fn write_and_await_ack(
+    state: &Arc<Mutex<SerialState>>,
+    connection_id: &str,
+    bytes: &[u8],
+    ack_pattern: &Regex,
+    timeout_ms: u64,
+) -> Result<String, String> {
+    let (request_id, rx, recorder) = {
+        let mut state = state.lock().await;
+        let connection = state
+            .connections
+            .get_mut(connection_id)
+            .ok_or_else(|| "Not connected".to_string())?;
+
+        let recorder =
+            write_port(connection, bytes).map_err(|e| format!("Write failed: {}", e))?;
+
+        let request_id = connection.next_request_id;
+        connection.next_request_id += 1;
+        let (tx, rx) = oneshot::channel();
+        connection.pending.push(PendingRequest {
+            id: request_id,
+            pattern: ack_pattern.clone(),
+            responder: tx,
+        });
+        (request_id, rx, recorder)
+    };
+    log_tx(recorder.as_ref(), bytes).await;
+
+    match tokio::time::timeout(Duration::from_millis(timeout_ms), rx).await {
+        Ok(Ok(line)) => Ok(line),
+        Ok(Err(_)) => Err("Reader task stopped before an ack arrived".to_string()),
+        Err(_) => {
+            let mut state = state.lock().await;
+            if let Some(connection) = state.connections.get_mut(connection_id) {
+                connection.pending.retain(|r| r.id != request_id);
+            }
+            Err(format!("Timed out after {}ms waiting for ack", timeout_ms))
+        }
+    }
+}
+
+/// Read incoming data on `connection_id` and return complete lines.
+///
+/// This is now a fallback path: when the background reader task is running (started by
+/// `connect_port`), lines normally arrive via the `serial-line:{connection_id}` event instead.
+/// The reader task owns the only read handle onto the port, so this just drains whatever it has
+/// already appended to `connection.buffer` rather than reading the port itself — a second reader
+/// racing the background task for the same bytes could steal a reply an outstanding
+/// `send_command_and_await` or `send_memory_sequence` is waiting on.
 #[tauri::command]
 pub async fn read_incoming(
     state: State<'_, Arc<Mutex<SerialState>>>,
+    connection_id: String,
 ) -> Result<ReadResult, String> {
     let mut state = state.lock().await;
-    let mut lines = Vec::new();
 
-    // First, read all available data from port into a temporary buffer
-    let mut incoming = Vec::new();
-    if let Some(ref mut port) = state.port {
-        let mut buf = [0u8; 256];
-        loop {
-            match port.read(&mut buf) {
-                Ok(n) if n > 0 => {
-                    incoming.extend_from_slice(&buf[..n]);
-                }
-                _ => break,
+    let connection = match state.connections.get_mut(&connection_id) {
+        Some(connection) => connection,
+        None => return Ok(ReadResult { lines: Vec::new() }),
+    };
+
+    let lines = drain_lines(&mut connection.buffer);
+
+    Ok(ReadResult { lines })
+}
+
+/// Start recording every TX/RX event on `connection_id` to a SQLite database at `path`, creating
+/// it if needed. Replaces any recording already in progress for that connection.
+#[tauri::command]
+pub async fn start_recording(
+    state: State<'_, Arc<Mutex<SerialState>>>,
+    connection_id: String,
+    path: String,
+) -> Result<SerialResult, String> {
+    let recorder = session_log::SessionRecorder::start(&path)
+        .await
+        .map_err(|e| format!("Failed to open session log: {}", e))?;
+    let session_id = recorder.session_id();
+
+    let mut state = state.lock().await;
+    let connection = match state.connections.get_mut(&connection_id) {
+        Some(connection) => connection,
+        None => {
+            return Ok(SerialResult {
+                success: false,
+                message: "Not connected".to_string(),
+            })
+        }
+    };
+    connection.recorder = Some(Arc::new(recorder));
+
+    Ok(SerialResult {
+        success: true,
+        message: format!("Recording session {} to {}", session_id, path),
+    })
+}
+
+/// Stop the active recording on `connection_id`, if any.
+#[tauri::command]
+pub async fn stop_recording(
+    state: State<'_, Arc<Mutex<SerialState>>>,
+    connection_id: String,
+) -> Result<SerialResult, String> {
+    let mut state = state.lock().await;
+    let stopped = state
+        .connections
+        .get_mut(&connection_id)
+        .map_or(false, |c| c.recorder.take().is_some());
+    Ok(SerialResult {
+        success: true,
+        message: if stopped {
+            "Recording stopped".to_string()
+        } else {
+            "No recording was in progress".to_string()
+        },
+    })
+}
+
+/// Dump a recorded session as CSV or JSON to `output_path`.
+#[tauri::command]
+pub async fn export_session(
+    db_path: String,
+    session_id: i64,
+    output_path: String,
+    format: String,
+) -> Result<SerialResult, String> {
+    let events = session_log::load_events(&db_path, session_id)
+        .await
+        .map_err(|e| format!("Failed to read session log: {}", e))?;
+
+    let rendered = match format.as_str() {
+        "csv" => session_log::to_csv(&events),
+        "json" => serde_json::to_string_pretty(&events)
+            .map_err(|e| format!("Failed to serialize events: {}", e))?,
+        other => {
+            return Ok(SerialResult {
+                success: false,
+                message: format!("Unknown export format: {}", other),
+            })
+        }
+    };
+
+    std::fs::write(&output_path, rendered).map_err(|e| format!("Failed to write export: {}", e))?;
+
+    Ok(SerialResult {
+        success: true,
+        message: format!("Exported {} events to {}", events.len(), output_path),
+    })
+}
+
+/// Replay a recorded session's TX bytes back out `connection_id`'s active port, preserving the
+/// original inter-event delays so a memory-programming bug can be reproduced offline.
+#[tauri::command]
+pub async fn replay_session(
+    state: State<'_, Arc<Mutex<SerialState>>>,
+    connection_id: String,
+    db_path: String,
+    session_id: i64,
+) -> Result<SerialResult, String> {
+    let events = session_log::load_events(&db_path, session_id)
+        .await
+        .map_err(|e| format!("Failed to read session log: {}", e))?;
+    let tx_events: Vec<_> = events
+        .into_iter()
+        .filter(|e| e.direction == Direction::Tx)
+        .collect();
+
+    {
+        let state = state.lock().await;
+        match state.connections.get(&connection_id) {
+            Some(connection) if connection.port.is_some() => {}
+            _ => {
+                return Ok(SerialResult {
+                    success: false,
+                    message: "Not connected".to_string(),
+                })
             }
         }
     }
 
-    // Now extend the state buffer (port borrow is released)
-    state.buffer.extend_from_slice(&incoming);
+    let mut last_ms: Option<i64> = None;
+    for event in &tx_events {
+        if let Some(prev) = last_ms {
+            let delay_ms = (event.monotonic_ms - prev).max(0) as u64;
+            if delay_ms > 0 {
+                // Sleep outside the lock so other connections' commands aren't blocked for the
+                // whole replay, same as `write_and_await_ack`.
+                tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            }
+        }
+        last_ms = Some(event.monotonic_ms);
 
-    // Extract complete lines
-    while let Some(pos) = state.buffer.iter().position(|&b| b == b'\n') {
-        let line_bytes: Vec<u8> = state.buffer.drain(..=pos).collect();
-        let mut line = String::from_utf8_lossy(&line_bytes).to_string();
-        // Trim \r\n
-        line = line.trim_end_matches(&['\r', '\n'][..]).to_string();
-        if !line.is_empty() {
-            lines.push(line);
+        let mut state = state.lock().await;
+        let connection = match state.connections.get_mut(&connection_id) {
+            Some(connection) if connection.port.is_some() => connection,
+            _ => {
+                return Ok(SerialResult {
+                    success: false,
+                    message: "Not connected".to_string(),
+                })
+            }
+        };
+        if let Err(e) = connection.port.as_mut().unwrap().write(&event.raw) {
+            return Ok(SerialResult {
+                success: false,
+                message: format!("Replay write failed: {}", e),
+            });
         }
     }
 
-    Ok(ReadResult { lines })
+    Ok(SerialResult {
+        success: true,
+        message: format!("Replayed {} TX events", tx_events.len()),
+    })
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial_backend::MockPort;
+
+    fn mock_connection() -> Connection {
+        let mut connection = Connection::new();
+        connection.port = Some(Box::new(MockPort::new()));
+        connection
+    }
+
+    // write_and_await_ack doesn't depend on the background reader task (which needs a real
+    // AppHandle), so it can be exercised directly by acting as the reader task would: matching
+    // the pending request against a line and resolving its responder.
+    async fn resolve_next_pending(state: &Arc<Mutex<SerialState>>, connection_id: &str, line: &str) {
+        let mut state = state.lock().await;
+        let connection = state.connections.get_mut(connection_id).unwrap();
+        let idx = connection
+            .pending
+            .iter()
+            .position(|r| r.pattern.is_match(line))
+            .expect("no pending request matched the line");
+        let pending = connection.pending.remove(idx);
+        let _ = pending.responder.send(line.to_string());
+    }
+
+    #[tokio::test]
+    async fn write_and_await_ack_resolves_once_the_ack_line_arrives() {
+        let state = Arc::new(Mutex::new(SerialState::new()));
+        state
+            .lock()
+            .await
+            .connections
+            .insert("a".to_string(), mock_connection());
+        let ack_pattern = Regex::new(r"^\d+$").unwrap();
+
+        let state_for_ack = Arc::clone(&state);
+        let ack = tokio::spawn(async move {
+            write_and_await_ack(&state_for_ack, "a", b"12345", &ack_pattern, 200).await
+        });
+
+        // Let the spawned task write and register its pending request before resolving it.
+        tokio::task::yield_now().await;
+        resolve_next_pending(&state, "a", "12345").await;
+
+        assert_eq!(ack.await.unwrap(), Ok("12345".to_string()));
+    }
+
+    #[tokio::test]
+    async fn write_and_await_ack_times_out_and_clears_the_pending_registration() {
+        let state = Arc::new(Mutex::new(SerialState::new()));
+        state
+            .lock()
+            .await
+            .connections
+            .insert("a".to_string(), mock_connection());
+        let ack_pattern = Regex::new(r"^\d+$").unwrap();
+
+        let result = write_and_await_ack(&state, "a", b"12345", &ack_pattern, 20).await;
+
+        assert!(result.is_err());
+        assert!(state
+            .lock()
+            .await
+            .connections
+            .get("a")
+            .unwrap()
+            .pending
+            .is_empty());
+    }
+}
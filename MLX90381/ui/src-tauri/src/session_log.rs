@@ -0,0 +1,188 @@
+use serde::Serialize;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::{Row, SqlitePool};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Which side of the link an event travelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Tx,
+    Rx,
+}
+
+impl Direction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Tx => "tx",
+            Direction::Rx => "rx",
+        }
+    }
+
+    fn from_str(s: &str) -> Self {
+        if s == "tx" {
+            Direction::Tx
+        } else {
+            Direction::Rx
+        }
+    }
+}
+
+/// One recorded TX/RX event: when it happened, the raw bytes on the wire, and (for RX) whatever
+/// the reader task managed to decode from them.
+#[derive(Debug, Serialize)]
+pub struct RecordedEvent {
+    pub session_id: i64,
+    pub direction: Direction,
+    pub monotonic_ms: i64,
+    pub wallclock_ms: i64,
+    pub raw: Vec<u8>,
+    pub decoded: Option<String>,
+}
+
+/// Owns the SQLite connection for one recording session and timestamps every logged event
+/// relative to when recording started.
+pub struct SessionRecorder {
+    pool: SqlitePool,
+    session_id: i64,
+    start: Instant,
+}
+
+impl SessionRecorder {
+    /// Open (creating if necessary) the database at `path` and start a new session.
+    pub async fn start(path: &str) -> Result<Self, sqlx::Error> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(&format!("sqlite://{}?mode=rwc", path))
+            .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                started_wallclock_ms INTEGER NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                direction TEXT NOT NULL,
+                monotonic_ms INTEGER NOT NULL,
+                wallclock_ms INTEGER NOT NULL,
+                raw BLOB NOT NULL,
+                decoded TEXT
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        // Row id from its own table, not wallclock_ms(), so two recordings started in the same
+        // millisecond (e.g. on two connections) still get distinct session ids.
+        let inserted = sqlx::query("INSERT INTO sessions (started_wallclock_ms) VALUES (?)")
+            .bind(wallclock_ms())
+            .execute(&pool)
+            .await?;
+        let session_id = inserted.last_insert_rowid();
+
+        Ok(Self {
+            pool,
+            session_id,
+            start: Instant::now(),
+        })
+    }
+
+    pub fn session_id(&self) -> i64 {
+        self.session_id
+    }
+
+    pub async fn log(
+        &self,
+        direction: Direction,
+        raw: &[u8],
+        decoded: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        let monotonic_ms = self.start.elapsed().as_millis() as i64;
+
+        sqlx::query(
+            "INSERT INTO events (session_id, direction, monotonic_ms, wallclock_ms, raw, decoded)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(self.session_id)
+        .bind(direction.as_str())
+        .bind(monotonic_ms)
+        .bind(wallclock_ms())
+        .bind(raw)
+        .bind(decoded)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+fn wallclock_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+/// Load every event recorded for `session_id` from the database at `db_path`, in the order they
+/// were logged. Used by `export_session` and `replay_session`, which don't need a live recorder.
+pub async fn load_events(db_path: &str, session_id: i64) -> Result<Vec<RecordedEvent>, sqlx::Error> {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect(&format!("sqlite://{}", db_path))
+        .await?;
+
+    let rows = sqlx::query(
+        "SELECT session_id, direction, monotonic_ms, wallclock_ms, raw, decoded
+         FROM events WHERE session_id = ? ORDER BY id",
+    )
+    .bind(session_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| RecordedEvent {
+            session_id: row.get("session_id"),
+            direction: Direction::from_str(row.get::<String, _>("direction").as_str()),
+            monotonic_ms: row.get("monotonic_ms"),
+            wallclock_ms: row.get("wallclock_ms"),
+            raw: row.get("raw"),
+            decoded: row.get("decoded"),
+        })
+        .collect())
+}
+
+/// Render events as CSV; bytes are hex-encoded so the output stays one line per event.
+pub fn to_csv(events: &[RecordedEvent]) -> String {
+    let mut out = String::from("session_id,direction,monotonic_ms,wallclock_ms,raw_hex,decoded\n");
+    for event in events {
+        let raw_hex = event
+            .raw
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        let decoded = event
+            .decoded
+            .as_deref()
+            .unwrap_or("")
+            .replace(['\r', '\n'], " ")
+            .replace(',', " ");
+        out.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            event.session_id,
+            event.direction.as_str(),
+            event.monotonic_ms,
+            event.wallclock_ms,
+            raw_hex,
+            decoded
+        ));
+    }
+    out
+}